@@ -1,3 +1,4 @@
+mod codegen;
 mod error;
 mod generation;
 mod input;
@@ -12,6 +13,7 @@ use self::{
     utility::{sanitize_documentation, sanitize_snake_case_identifier},
 };
 use convert_case::{Case, Casing};
+pub use codegen::{generate_dialect_file, OutputMode, Rustfmt};
 pub use input::DialectInput;
 use operation::Operation;
 use proc_macro::TokenStream;
@@ -28,6 +30,16 @@ use tblgen::{TableGenParser, record::Record, record_keeper::RecordKeeper};
 const LLVM_INCLUDE_DIRECTORY: &str = env!("LLVM_INCLUDE_DIRECTORY");
 
 pub fn generate_dialect(input: DialectInput) -> Result<TokenStream, Box<dyn std::error::Error>> {
+    Ok(generate_dialect_tokens(&input)?.into())
+}
+
+// Shared by the proc-macro entry point above and the standalone,
+// file-emitting entry point in `codegen`, so that the expensive TableGen
+// parse can be driven from a `build.rs` or an xtask binary instead of on
+// every proc-macro expansion.
+pub(crate) fn generate_dialect_tokens(
+    input: &DialectInput,
+) -> Result<proc_macro2::TokenStream, Box<dyn std::error::Error>> {
     let mut parser = TableGenParser::new();
 
     parser = parser.add_include_directory(LLVM_INCLUDE_DIRECTORY);
@@ -53,17 +65,160 @@ pub fn generate_dialect(input: DialectInput) -> Result<TokenStream, Box<dyn std:
 
     let keeper = parser.parse().map_err(Error::Parse)?;
 
-    let dialect = generate_dialect_module(
-        input.name(),
-        keeper
-            .all_derived_definitions("Dialect")
-            .find(|definition| definition.str_value("name") == Ok(input.name()))
-            .ok_or_else(|| create_syn_error("dialect not found"))?,
-        &keeper,
-    )
-    .map_err(|error| error.add_source_info(keeper.source_info()))?;
-
-    Ok(quote! { #dialect }.into())
+    generate_dialects_tokens(input.name(), &keeper)
+}
+
+// Generates a single named dialect when `name` is given, matching the
+// proc-macro's current one-dialect-per-invocation usage. When `name` is
+// `None`, every `Dialect` record found by the parser is emitted as its own
+// module, plus a top-level `AnyOperation` enum that can classify an
+// operation from any of them.
+fn generate_dialects_tokens(
+    name: Option<&str>,
+    keeper: &RecordKeeper,
+) -> Result<proc_macro2::TokenStream, Box<dyn std::error::Error>> {
+    let dialects = match name {
+        Some(name) => vec![
+            keeper
+                .all_derived_definitions("Dialect")
+                .find(|definition| definition.str_value("name") == Ok(name))
+                .ok_or_else(|| create_syn_error("dialect not found"))?,
+        ],
+        None => keeper.all_derived_definitions("Dialect").collect(),
+    };
+
+    let dialect_names = dialects
+        .iter()
+        .map(|dialect| {
+            Ok((
+                dialect.name()?.to_string(),
+                dialect.str_value("name")?.to_string(),
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()
+        .map_err(|error| error.add_source_info(keeper.source_info()))?;
+
+    let modules = dialects
+        .into_iter()
+        .zip(&dialect_names)
+        .map(|(dialect, (_, name))| generate_dialect_module(name, dialect, keeper))
+        .collect::<Result<Vec<_>, Error>>()
+        .map_err(|error| error.add_source_info(keeper.source_info()))?;
+
+    let any_operation = if name.is_none() {
+        let operation_dialect_names = dialect_names
+            .iter()
+            .map(|(record_name, name)| {
+                Ok(dialect_has_operations(record_name, keeper)?
+                    .then(|| (record_name.clone(), name.clone())))
+            })
+            .collect::<Result<Vec<_>, Error>>()
+            .map_err(|error| error.add_source_info(keeper.source_info()))?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if operation_dialect_names.is_empty() {
+            None
+        } else {
+            Some(
+                generate_any_operation_enum(&operation_dialect_names)
+                    .map_err(|error| error.add_source_info(keeper.source_info()))?,
+            )
+        }
+    } else {
+        None
+    };
+
+    Ok(quote! {
+        #(#modules)*
+        #any_operation
+    })
+}
+
+// Each entry of `dialect_names` pairs a dialect's record name (which names
+// its operation enum) with its `name` field (which names its module and is
+// the prefix of its operation names).
+fn generate_any_operation_enum(
+    dialect_names: &[(String, String)],
+) -> Result<proc_macro2::TokenStream, Error> {
+    let mut variants = Vec::new();
+    let mut try_new_arms = Vec::new();
+    let mut as_operation_arms = Vec::new();
+    let mut clone_arms = Vec::new();
+
+    for (record_name, dialect_name) in dialect_names {
+        let variant = quote::format_ident!("{}", record_name.to_case(Case::Pascal));
+        let enum_type = quote::format_ident!("{}Operation", record_name.to_case(Case::Pascal));
+        let module = sanitize_snake_case_identifier(dialect_name)?;
+
+        variants.push(quote! { #variant(#module::#enum_type<'b>) });
+        try_new_arms.push(quote! {
+            #dialect_name => #module::#enum_type::try_new(operation).map(AnyOperation::#variant),
+        });
+        as_operation_arms.push(quote! {
+            AnyOperation::#variant(operation) => operation.as_operation(),
+        });
+        clone_arms.push(quote! {
+            AnyOperation::#variant(operation) => AnyOperation::#variant(operation.clone()),
+        });
+    }
+
+    Ok(quote! {
+        pub enum AnyOperation<'b> {
+            #(#variants),*
+        }
+
+        impl<'b> Clone for AnyOperation<'b> {
+            fn clone(&self) -> Self {
+                match self {
+                    #(#clone_arms)*
+                }
+            }
+        }
+
+        impl<'b> std::fmt::Display for AnyOperation<'b> {
+            fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(self.as_operation(), formatter)
+            }
+        }
+
+        impl<'b> AnyOperation<'b> {
+            /// Classifies `operation` by dispatching on its dialect prefix
+            /// before delegating to that dialect's own operation enum.
+            pub fn try_new(
+                operation: melior::ir::operation::Operation<'b>,
+            ) -> Result<Self, melior::ir::operation::Operation<'b>> {
+                match operation
+                    .name()
+                    .as_string_ref()
+                    .as_str()
+                    .unwrap()
+                    .split('.')
+                    .next()
+                    .unwrap_or("")
+                {
+                    #(#try_new_arms)*
+                    _ => Err(operation),
+                }
+            }
+
+            pub fn as_operation(&self) -> &melior::ir::operation::Operation<'b> {
+                match self {
+                    #(#as_operation_arms)*
+                }
+            }
+        }
+    })
+}
+
+fn dialect_has_operations(dialect_name: &str, record_keeper: &RecordKeeper) -> Result<bool, Error> {
+    Ok(record_keeper
+        .all_derived_definitions("Op")
+        .map(Operation::new)
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .any(|operation| operation.dialect_name() == dialect_name))
 }
 
 fn generate_operation_enum(
@@ -133,6 +288,23 @@ fn generate_operation_enum(
         }
     });
 
+    let try_into_impls = operations.iter().map(|operation| {
+        let ident = quote::format_ident!("{}", operation.name());
+
+        quote! {
+            impl<'b> TryFrom<#enum_ident<'b>> for #ident<'b> {
+                type Error = #enum_ident<'b>;
+
+                fn try_from(operation: #enum_ident<'b>) -> Result<Self, Self::Error> {
+                    match operation {
+                        #enum_ident::#ident(op) => Ok(op),
+                        other => Err(other),
+                    }
+                }
+            }
+        }
+    });
+
     if operation_enum.is_empty() {
         Ok(None)
     } else {
@@ -172,12 +344,117 @@ fn generate_operation_enum(
                 }
             }
 
+            impl<'b> std::ops::Deref for #enum_name<'b> {
+                type Target = melior::ir::operation::Operation<'b>;
+
+                fn deref(&self) -> &Self::Target {
+                    self.as_operation()
+                }
+            }
+
+            impl<'b> AsRef<melior::ir::operation::Operation<'b>> for #enum_name<'b> {
+                fn as_ref(&self) -> &melior::ir::operation::Operation<'b> {
+                    self.as_operation()
+                }
+            }
+
             #(#from_impls)*
+
+            #(#try_into_impls)*
         };
         Ok(Some(enum_definition))
     }
 }
 
+fn definition_dialect_name(record: &Record) -> Option<String> {
+    record
+        .def_value("dialect")
+        .ok()?
+        .str_value("name")
+        .ok()
+        .map(str::to_string)
+}
+
+fn generate_definition(
+    definition: Record,
+    dialect_name: &str,
+    handle_type: &proc_macro2::TokenStream,
+    kind: &str,
+    sigil: char,
+) -> Result<proc_macro2::TokenStream, Error> {
+    let ident = quote::format_ident!("{}", definition.name()?);
+    let field = syn::parse_str::<syn::Ident>(kind)
+        .unwrap_or_else(|_| quote::format_ident!("r#{}", kind));
+    let mnemonic = definition.str_value("mnemonic")?;
+    let full_name = format!("{sigil}{dialect_name}.{mnemonic}");
+    let doc = format!(
+        "`{full_name}` {kind}.\n\n{}",
+        sanitize_documentation(definition.str_value("description").unwrap_or(""))?
+    );
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct #ident<'c> {
+            #field: #handle_type<'c>,
+        }
+
+        impl<'c> #ident<'c> {
+            pub fn new(#field: #handle_type<'c>) -> Option<Self> {
+                Self::try_from(#field).ok()
+            }
+
+            pub fn #field(&self) -> &#handle_type<'c> {
+                &self.#field
+            }
+        }
+
+        impl<'c> TryFrom<#handle_type<'c>> for #ident<'c> {
+            type Error = #handle_type<'c>;
+
+            fn try_from(#field: #handle_type<'c>) -> Result<Self, Self::Error> {
+                // Require a delimiter boundary right after the mnemonic so a
+                // mnemonic that is a prefix of another in the same dialect
+                // (e.g. `ptr` vs `ptrx`) isn't mistakenly accepted.
+                let matches = #field
+                    .to_string()
+                    .strip_prefix(#full_name)
+                    .is_some_and(|rest| {
+                        matches!(rest.chars().next(), None | Some('<' | ',' | '>'))
+                            || rest.starts_with(char::is_whitespace)
+                    });
+
+                if matches {
+                    Ok(Self { #field })
+                } else {
+                    Err(#field)
+                }
+            }
+        }
+
+        impl<'c> std::fmt::Display for #ident<'c> {
+            fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(&self.#field, formatter)
+            }
+        }
+    })
+}
+
+fn generate_definitions(
+    dialect_name: &str,
+    record_keeper: &RecordKeeper,
+    class_name: &str,
+    handle_type: proc_macro2::TokenStream,
+    kind: &str,
+    sigil: char,
+) -> Result<Vec<proc_macro2::TokenStream>, Error> {
+    record_keeper
+        .all_derived_definitions(class_name)
+        .filter(|record| definition_dialect_name(record).as_deref() == Some(dialect_name))
+        .map(|record| generate_definition(record, dialect_name, &handle_type, kind, sigil))
+        .collect()
+}
+
 fn generate_dialect_module(
     name: &str,
     dialect: Record,
@@ -192,6 +469,22 @@ fn generate_dialect_module(
         .filter(|operation| operation.dialect_name() == dialect_name)
         .map(generate_operation)
         .collect::<Vec<_>>();
+    let types = generate_definitions(
+        name,
+        record_keeper,
+        "TypeDef",
+        quote! { melior::ir::r#type::Type },
+        "type",
+        '!',
+    )?;
+    let attributes = generate_definitions(
+        name,
+        record_keeper,
+        "AttrDef",
+        quote! { melior::ir::attribute::Attribute },
+        "attribute",
+        '#',
+    )?;
 
     let doc = format!(
         "`{name}` dialect.\n\n{}",
@@ -208,6 +501,10 @@ fn generate_dialect_module(
 
             #(#operations)*
 
+            #(#types)*
+
+            #(#attributes)*
+
             #enum_definition
         }
     })
@@ -0,0 +1,119 @@
+use std::{
+    error::Error,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use super::{generate_dialect_tokens, DialectInput};
+
+/// How [`generate_dialect_file`] should reconcile generated source with the
+/// file on disk.
+///
+/// Mirrors the overwrite/verify split used by rust-analyzer's codegen: a
+/// project commits the generated bindings and runs `Verify` in CI to catch
+/// drift, regenerating them locally with `Overwrite` when the TableGen
+/// sources change.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputMode {
+    /// Write the generated source to `path`, replacing its current contents.
+    Overwrite,
+    /// Generate the source in memory and compare it against `path` byte for
+    /// byte, returning an error if they differ.
+    Verify,
+}
+
+/// How the source emitted by [`generate_dialect_file`] should be formatted
+/// before it is written or compared.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Rustfmt {
+    /// Do not format the generated source.
+    Disabled,
+    /// Format with the `rustfmt` binary found on `PATH`.
+    #[default]
+    Discover,
+    /// Format with the `rustfmt` binary at the given path.
+    Path(PathBuf),
+}
+
+/// Generates a dialect from `input` and, depending on `mode`, writes it to
+/// `path` or checks it against `path`, instead of returning a
+/// `proc_macro::TokenStream`.
+///
+/// This is meant to be called from a `build.rs` script or a standalone xtask
+/// binary so that the TableGen parse runs only when the bindings are
+/// regenerated, not on every compilation of the crate that uses them.
+pub fn generate_dialect_file(
+    input: DialectInput,
+    path: &Path,
+    mode: OutputMode,
+    rustfmt: Rustfmt,
+) -> Result<(), Box<dyn Error>> {
+    let generated = generate_dialect_tokens(&input)?.to_string();
+    let generated = format_with_rustfmt(&generated, &rustfmt);
+
+    match mode {
+        OutputMode::Overwrite => {
+            fs::write(path, generated)?;
+
+            Ok(())
+        }
+        OutputMode::Verify => {
+            let existing = fs::read_to_string(path)?;
+
+            if existing == generated {
+                Ok(())
+            } else {
+                Err(format!(
+                    "generated dialect bindings at {} are out of date; regenerate them with `OutputMode::Overwrite`",
+                    path.display()
+                )
+                .into())
+            }
+        }
+    }
+}
+
+// Formats `source` with `rustfmt`, falling back to the unformatted source if
+// formatting is disabled or the binary cannot be spawned. Committed,
+// diffable bindings should be readable, and `Verify` mode needs formatting
+// to be applied consistently so it isn't tripped up by formatting churn
+// alone.
+fn format_with_rustfmt(source: &str, rustfmt: &Rustfmt) -> String {
+    let binary: &Path = match rustfmt {
+        Rustfmt::Disabled => return source.into(),
+        Rustfmt::Discover => Path::new("rustfmt"),
+        Rustfmt::Path(path) => path,
+    };
+
+    run_rustfmt(binary, source).unwrap_or_else(|| source.into())
+}
+
+fn run_rustfmt(binary: &Path, source: &str) -> Option<String> {
+    let mut child = Command::new(binary)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let mut stdin = child.stdin.take()?;
+    let source = source.to_owned();
+
+    // rustfmt writes the formatted file back to stdout as it reads stdin, so
+    // writing the whole source in this thread before draining stdout would
+    // deadlock once the source exceeds the stdout pipe buffer: rustfmt
+    // blocks writing output while we're still blocked writing input. Write
+    // on a separate thread instead so both pipes drain concurrently.
+    let writer = std::thread::spawn(move || stdin.write_all(source.as_bytes()));
+
+    let output = child.wait_with_output().ok()?;
+    writer.join().ok()?.ok()?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}